@@ -1,20 +1,46 @@
+use futures::StreamExt;
 use log::debug;
 use prometheus::{Counter, Gauge, register_gauge, Histogram, exponential_buckets, register_histogram, register_counter};
 use crate::docker;
 use crate::Config;
 
 mod trackers {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
     use log::debug;
     use prometheus::{opts, labels, Gauge, register_gauge};
     use crate::docker;
 
+    /// Turn a Docker label key (e.g. `com.docker.compose.project`) into a valid Prometheus label
+    /// name matching `[a-zA-Z_][a-zA-Z0-9_]*`: every character outside `[a-zA-Z0-9_]` becomes an
+    /// underscore, and a leading digit is prefixed with one so the result is a legal identifier.
+    fn sanitize_label_name(key: &str) -> String {
+        let mut name: String = key.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+
+        if name.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+            name.insert(0, '_');
+        }
+
+        name
+    }
+
     pub struct ContainerTracker {
         pub id: String,
         cpu_usage: Gauge,
         cpu_capacity: Gauge,
+        cpu_percent: Gauge,
+        // Previous scrape's CPU counters, used to turn the cumulative totals into a utilization
+        // percentage the way `docker stats` does. `has_prev_cpu` guards the very first scrape.
+        prev_cpu_total: AtomicU64,
+        prev_system_cpu: AtomicU64,
+        has_prev_cpu: AtomicBool,
         memory_usage: Gauge,
         restart_count: Gauge,
         running_state: Gauge,
+        health: Gauge,
+        health_failing_streak: Gauge,
+        exit_code: Gauge,
+        oom_killed: Gauge,
         start_time: Gauge,
         total_bytes_in: Gauge,
         total_bytes_out: Gauge,
@@ -23,26 +49,53 @@ mod trackers {
     }
 
     impl ContainerTracker {
-        pub fn new(c: docker::Container) -> ContainerTracker {
+        pub fn new(c: docker::Container, expose_labels: &[String]) -> ContainerTracker {
             let name = Self::get_display_name(&c);
-            let cpu_usage = register_gauge!(opts!("docker_container_cpu_used_total", "Accumulated CPU usage of a container, in unspecified units, averaged for all logical CPUs usable by the container.", labels! { "name" => &name })).unwrap();
-            let cpu_capacity = register_gauge!(opts!("docker_container_cpu_capacity_total", "All potential CPU usage available to a container, in unspecified units, averaged for all logical CPUs usable by the container. Start point of measurement is undefined - only relative values should be used in analytics.", labels! { "name" => &name })).unwrap();
-            let memory_usage = register_gauge!(opts!("docker_container_memory_used_bytes", "Memory usage of a container.", labels! { "name" => &name })).unwrap();
-            let restart_count = register_gauge!(opts!("docker_container_restart_count", "Number of times the runtime has restarted this container without explicit user action, since the container was last started.", labels! { "name" => &name })).unwrap();
-            let running_state = register_gauge!(opts!("docker_container_running_state", "Whether the container is running (1), restarting (0.5) or stopped (0).", labels! { "name" => &name })).unwrap();
-            let start_time = register_gauge!(opts!("docker_container_start_time_seconds", "Timestamp indicating when the container was started. Does not get reset by automatic restarts.", labels! { "name" => &name })).unwrap();
-            let total_bytes_in = register_gauge!(opts!("docker_container_network_in_bytes", "Total bytes received by the container's network interfaces.", labels! { "name" => &name })).unwrap();
-            let total_bytes_out = register_gauge!(opts!("docker_container_network_out_bytes", "Total bytes sent by the container's network interfaces.", labels! { "name" => &name })).unwrap();
-            let total_bytes_read = register_gauge!(opts!("docker_container_disk_read_bytes", "Total bytes read from disk by a container.", labels! { "name" => &name })).unwrap();
-            let total_bytes_written = register_gauge!(opts!("docker_container_disk_write_bytes", "Total bytes written to disk by a container.", labels! { "name" => &name })).unwrap();
+
+            // Label sets are fixed when a gauge is registered, so build the full dimension map once
+            // from `name` plus any configured Docker labels and clone it into every series.
+            let mut const_labels = HashMap::new();
+            const_labels.insert("name".to_string(), name.clone());
+
+            for key in expose_labels {
+                let label = sanitize_label_name(key);
+
+                if !label.is_empty() {
+                    const_labels.insert(label, c.Labels.get(key).cloned().unwrap_or_default());
+                }
+            }
+
+            let cpu_usage = register_gauge!(opts!("docker_container_cpu_used_total", "Accumulated CPU usage of a container, in unspecified units, averaged for all logical CPUs usable by the container.", const_labels.clone())).unwrap();
+            let cpu_capacity = register_gauge!(opts!("docker_container_cpu_capacity_total", "All potential CPU usage available to a container, in unspecified units, averaged for all logical CPUs usable by the container. Start point of measurement is undefined - only relative values should be used in analytics.", const_labels.clone())).unwrap();
+            let cpu_percent = register_gauge!(opts!("docker_container_cpu_percent", "CPU utilization of a container in percent of one CPU (100 per fully used CPU, up to 100 times the number of online CPUs), computed from the delta between consecutive scrapes.", const_labels.clone())).unwrap();
+            let memory_usage = register_gauge!(opts!("docker_container_memory_used_bytes", "Memory usage of a container.", const_labels.clone())).unwrap();
+            let restart_count = register_gauge!(opts!("docker_container_restart_count", "Number of times the runtime has restarted this container without explicit user action, since the container was last started.", const_labels.clone())).unwrap();
+            let running_state = register_gauge!(opts!("docker_container_running_state", "Whether the container is running (1), restarting (0.5) or stopped (0).", const_labels.clone())).unwrap();
+            let health = register_gauge!(opts!("docker_container_health", "Health check status of a container: no health check (-1), starting (0), healthy (1) or unhealthy (2).", const_labels.clone())).unwrap();
+            let health_failing_streak = register_gauge!(opts!("docker_container_health_failing_streak", "Number of consecutive health check failures for a container.", const_labels.clone())).unwrap();
+            let exit_code = register_gauge!(opts!("docker_container_exit_code", "Exit code reported by the last run of a container.", const_labels.clone())).unwrap();
+            let oom_killed = register_gauge!(opts!("docker_container_oom_killed", "Whether the container was killed by the out-of-memory killer (1) or not (0).", const_labels.clone())).unwrap();
+            let start_time = register_gauge!(opts!("docker_container_start_time_seconds", "Timestamp indicating when the container was started. Does not get reset by automatic restarts.", const_labels.clone())).unwrap();
+            let total_bytes_in = register_gauge!(opts!("docker_container_network_in_bytes", "Total bytes received by the container's network interfaces.", const_labels.clone())).unwrap();
+            let total_bytes_out = register_gauge!(opts!("docker_container_network_out_bytes", "Total bytes sent by the container's network interfaces.", const_labels.clone())).unwrap();
+            let total_bytes_read = register_gauge!(opts!("docker_container_disk_read_bytes", "Total bytes read from disk by a container.", const_labels.clone())).unwrap();
+            let total_bytes_written = register_gauge!(opts!("docker_container_disk_write_bytes", "Total bytes written to disk by a container.", const_labels.clone())).unwrap();
             
             ContainerTracker {
                 id: c.Id,
                 cpu_usage,
                 cpu_capacity,
+                cpu_percent,
+                prev_cpu_total: AtomicU64::new(0),
+                prev_system_cpu: AtomicU64::new(0),
+                has_prev_cpu: AtomicBool::new(false),
                 memory_usage,
                 restart_count,
                 running_state,
+                health,
+                health_failing_streak,
+                exit_code,
+                oom_killed,
                 start_time,
                 total_bytes_in,
                 total_bytes_out,
@@ -64,6 +117,16 @@ mod trackers {
             self.running_state.set(if inspect.State.Running { 1. } else if inspect.State.Restarting { 0.5 } else { 0. });
             self.restart_count.set(inspect.RestartCount as f64);
 
+            self.health.set(match inspect.State.Health.as_ref().map(|h| h.Status.as_str()) {
+                Some("healthy") => 1.,
+                Some("unhealthy") => 2.,
+                Some("starting") => 0.,
+                _ => -1.
+            });
+            self.health_failing_streak.set(inspect.State.Health.as_ref().map_or(0, |h| h.FailingStreak) as f64);
+            self.exit_code.set(inspect.State.ExitCode as f64);
+            self.oom_killed.set(if inspect.State.OOMKilled { 1. } else { 0. });
+
             if let Ok(d) = chrono::DateTime::parse_from_rfc3339(&inspect.State.StartedAt) {
                 let t = d.timestamp();
 
@@ -77,9 +140,28 @@ mod trackers {
             }
 
             let stats = docker::get_container_stats(&self.id).await?;
-            self.cpu_usage.set(stats.cpu_stats.cpu_usage.total_usage as f64);
-            self.cpu_capacity.set(stats.cpu_stats.system_cpu_usage as f64);
-            
+            let total_usage = stats.cpu_stats.cpu_usage.total_usage;
+            let system_cpu = stats.cpu_stats.system_cpu_usage;
+            self.cpu_usage.set(total_usage as f64);
+            self.cpu_capacity.set(system_cpu as f64);
+
+            let online_cpus = match stats.cpu_stats.online_cpus {
+                0 => stats.cpu_stats.cpu_usage.percpu_usage.len().max(1) as u64,
+                n => n
+            };
+
+            if self.has_prev_cpu.swap(true, Ordering::Relaxed) {
+                let cpu_delta = total_usage.saturating_sub(self.prev_cpu_total.load(Ordering::Relaxed));
+                let system_delta = system_cpu.saturating_sub(self.prev_system_cpu.load(Ordering::Relaxed));
+
+                if system_delta > 0 {
+                    self.cpu_percent.set(cpu_delta as f64 / system_delta as f64 * online_cpus as f64 * 100.0);
+                }
+            }
+
+            self.prev_cpu_total.store(total_usage, Ordering::Relaxed);
+            self.prev_system_cpu.store(system_cpu, Ordering::Relaxed);
+
             let tmp = stats.memory_stats.stats
                 .get("total_inactive_file").copied()
                 .or_else(|| stats.memory_stats.stats.get("inactive_file").copied())
@@ -101,9 +183,14 @@ mod trackers {
         fn drop(&mut self) {
             debug!("Dropping container tracker {}", self.id);
             prometheus::unregister(Box::new(self.cpu_usage.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.cpu_percent.clone())).unwrap_or(());
             prometheus::unregister(Box::new(self.memory_usage.clone())).unwrap_or(());
             prometheus::unregister(Box::new(self.restart_count.clone())).unwrap_or(());
             prometheus::unregister(Box::new(self.running_state.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.health.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.health_failing_streak.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.exit_code.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.oom_killed.clone())).unwrap_or(());
             prometheus::unregister(Box::new(self.start_time.clone())).unwrap_or(());
             prometheus::unregister(Box::new(self.total_bytes_in.clone())).unwrap_or(());
             prometheus::unregister(Box::new(self.total_bytes_out.clone())).unwrap_or(());
@@ -182,33 +269,146 @@ mod trackers {
             prometheus::unregister(Box::new(self.size.clone())).unwrap_or(());
         }
     }
+
+    /// Docker's default storage location, used to pick the filesystem whose usage we report.
+    const DOCKER_DATA_ROOT: &str = "/var/lib/docker";
+
+    pub struct HostTracker {
+        host: String,
+        system: sysinfo::System,
+        memory_total: Gauge,
+        memory_used: Gauge,
+        cpu_usage: Gauge,
+        core_usage: Vec<Gauge>,
+        load: [Gauge; 3],
+        filesystem_size: Gauge,
+        filesystem_used: Gauge,
+        tcp_connections: Gauge
+    }
+
+    impl HostTracker {
+        pub fn new() -> HostTracker {
+            let host = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
+            let system = sysinfo::System::new_all();
+
+            let memory_total = register_gauge!(opts!("docker_host_memory_total_bytes", "Total physical memory installed on the host.", labels! { "host" => &host })).unwrap();
+            let memory_used = register_gauge!(opts!("docker_host_memory_used_bytes", "Physical memory in use on the host.", labels! { "host" => &host })).unwrap();
+            let cpu_usage = register_gauge!(opts!("docker_host_cpu_usage_percent", "Aggregate CPU utilization of the host across all cores.", labels! { "host" => &host })).unwrap();
+
+            let core_usage = system.cpus().iter().map(|cpu| {
+                register_gauge!(opts!("docker_host_cpu_core_usage_percent", "Per-core CPU utilization of the host.", labels! { "host" => &host, "core" => cpu.name() })).unwrap()
+            }).collect();
+
+            let load = ["1", "5", "15"].map(|period| {
+                register_gauge!(opts!("docker_host_load_average", "Host load average over the given period in minutes.", labels! { "host" => &host, "period" => period })).unwrap()
+            });
+
+            let filesystem_size = register_gauge!(opts!("docker_host_filesystem_size_bytes", "Total size of the filesystem backing the Docker data root.", labels! { "host" => &host, "mountpoint" => DOCKER_DATA_ROOT })).unwrap();
+            let filesystem_used = register_gauge!(opts!("docker_host_filesystem_used_bytes", "Used space on the filesystem backing the Docker data root.", labels! { "host" => &host, "mountpoint" => DOCKER_DATA_ROOT })).unwrap();
+            let tcp_connections = register_gauge!(opts!("docker_host_tcp_connections", "Number of TCP sockets in any state visible in the exporter's network namespace. Run the exporter with host networking (and a host /proc mount) for a host-wide count.", labels! { "host" => &host })).unwrap();
+
+            HostTracker {
+                host,
+                system,
+                memory_total,
+                memory_used,
+                cpu_usage,
+                core_usage,
+                load,
+                filesystem_size,
+                filesystem_used,
+                tcp_connections
+            }
+        }
+
+        pub fn update(&mut self) {
+            self.system.refresh_memory();
+            self.system.refresh_cpu_usage();
+
+            self.memory_total.set(self.system.total_memory() as f64);
+            self.memory_used.set(self.system.used_memory() as f64);
+
+            self.cpu_usage.set(self.system.global_cpu_info().cpu_usage() as f64);
+            for (gauge, cpu) in self.core_usage.iter().zip(self.system.cpus()) {
+                gauge.set(cpu.cpu_usage() as f64);
+            }
+
+            let load = sysinfo::System::load_average();
+            self.load[0].set(load.one);
+            self.load[1].set(load.five);
+            self.load[2].set(load.fifteen);
+
+            // Report the filesystem that actually hosts the Docker data root (the mount point that
+            // is the longest prefix of the data root path).
+            let disks = sysinfo::Disks::new_with_refreshed_list();
+            if let Some(disk) = disks.iter().filter(|d| std::path::Path::new(DOCKER_DATA_ROOT).starts_with(d.mount_point())).max_by_key(|d| d.mount_point().as_os_str().len()) {
+                self.filesystem_size.set(disk.total_space() as f64);
+                self.filesystem_used.set((disk.total_space() - disk.available_space()) as f64);
+            }
+
+            self.tcp_connections.set(Self::count_tcp_connections() as f64);
+        }
+
+        /// Count TCP sockets in every state (LISTEN, ESTABLISHED, TIME_WAIT, ...) from
+        /// `/proc/net/tcp{,6}`. This reflects the exporter's own network namespace unless it runs
+        /// on host networking with the host's `/proc` mounted.
+        fn count_tcp_connections() -> usize {
+            ["/proc/net/tcp", "/proc/net/tcp6"].iter()
+                .filter_map(|p| std::fs::read_to_string(p).ok())
+                .map(|contents| contents.lines().skip(1).count())
+                .sum()
+        }
+    }
+
+    impl Drop for HostTracker {
+        fn drop(&mut self) {
+            debug!("Dropping host tracker {}", self.host);
+            prometheus::unregister(Box::new(self.memory_total.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.memory_used.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.cpu_usage.clone())).unwrap_or(());
+            for gauge in &self.core_usage {
+                prometheus::unregister(Box::new(gauge.clone())).unwrap_or(());
+            }
+            for gauge in &self.load {
+                prometheus::unregister(Box::new(gauge.clone())).unwrap_or(());
+            }
+            prometheus::unregister(Box::new(self.filesystem_size.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.filesystem_used.clone())).unwrap_or(());
+            prometheus::unregister(Box::new(self.tcp_connections.clone())).unwrap_or(());
+        }
+    }
 }
 
 use trackers::*;
 
 pub struct Collector {
     container_count: Gauge,
+    last_scrape_timestamp: Gauge,
     probe_duration: Histogram,
     probe_failures: Counter,
     container_trackers: Vec<ContainerTracker>,
     volume_trackers: Vec<VolumeTracker>,
-    image_trackers: Vec<ImageTracker>
+    image_trackers: Vec<ImageTracker>,
+    host_tracker: Option<HostTracker>
 }
 
 impl Collector {
     pub fn new() -> Collector {
         let buckets = exponential_buckets(1.0, 2.0, 7).unwrap();
         let container_count = register_gauge!("docker_containers", "Number of containers that exist.").unwrap();
+        let last_scrape_timestamp = register_gauge!("docker_last_scrape_timestamp_seconds", "Unix timestamp of the last successful Docker probe, so snapshot staleness is observable.").unwrap();
         let probe_duration = register_histogram!("docker_probe_duration_seconds", "How long it takes to query Docker for the complete data set.", buckets).unwrap();
         let probe_failures = register_counter!("docker_probe_failures_total", "The number of times any individual Docker query failed (because of a timeout or other reasons).").unwrap();
 
         Collector {
             container_count,
+            last_scrape_timestamp,
             probe_duration,
             probe_failures,
             container_trackers: Vec::new(),
             volume_trackers: Vec::new(),
-            image_trackers: Vec::new()
+            image_trackers: Vec::new(),
+            host_tracker: None
         }
     }
 
@@ -231,13 +431,18 @@ impl Collector {
                 for c in listed_containers {
                     if !self.container_trackers.iter().any(|p| p.id == c.Id) {
                         debug!("Adding container tracker {}", c.Id);
-                        self.container_trackers.push(ContainerTracker::new(c));
+                        self.container_trackers.push(ContainerTracker::new(c, &config.expose_labels));
                     }
                 }
 
                 self.container_count.set(self.container_trackers.len() as f64);
 
-                let update_results = futures::future::join_all(self.container_trackers.iter().map(|c| c.update())).await;
+                // Throttle the per-container inspect/stats fan-out so a host with many containers
+                // doesn't hammer the daemon all at once, while still overlapping I/O up to the limit.
+                let update_results: Vec<Option<()>> = futures::stream::iter(self.container_trackers.iter().map(|c| c.update()))
+                    .buffer_unordered(config.max_concurrent_probes)
+                    .collect()
+                    .await;
 
                 match update_results.iter().filter(|x| x.is_none()).count() {
                     x if x > 0 => self.probe_failures.inc_by(x as f64),
@@ -272,6 +477,14 @@ impl Collector {
                     }
                 }
 
+                if config.collect_host_metrics {
+                    self.host_tracker.get_or_insert_with(HostTracker::new).update();
+                } else {
+                    self.host_tracker = None;
+                }
+
+                self.last_scrape_timestamp.set(chrono::Utc::now().timestamp() as f64);
+
                 true
             }
             _ => {
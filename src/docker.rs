@@ -7,7 +7,16 @@ mod contract {
     #[derive(Deserialize)]
     pub struct Container {
         pub Id: String,
-        pub Names: Vec<String>
+        pub Names: Vec<String>,
+        #[serde(deserialize_with = "deserialize_null_default", default)]
+        pub Labels: HashMap<String, String>
+    }
+
+    #[derive(Deserialize)]
+    pub struct Health {
+        pub Status: String,
+        #[serde(default)]
+        pub FailingStreak: u32
     }
 
     #[derive(Deserialize)]
@@ -15,7 +24,15 @@ mod contract {
         pub Running: bool,
         pub Restarting: bool,
         #[serde(deserialize_with = "deserialize_null_default", default)]
-        pub StartedAt: String
+        pub StartedAt: String,
+        #[serde(default)]
+        pub Health: Option<Health>,
+        #[serde(default)]
+        pub ExitCode: i64,
+        #[serde(default)]
+        pub OOMKilled: bool,
+        #[serde(deserialize_with = "deserialize_null_default", default)]
+        pub Error: String
     }
 
     #[derive(Deserialize)]
@@ -34,14 +51,18 @@ mod contract {
 
     #[derive(Default, Deserialize)]
     pub struct CpuUsage {
-        pub total_usage: u64
+        pub total_usage: u64,
+        #[serde(deserialize_with = "deserialize_null_default", default)]
+        pub percpu_usage: Vec<u64>
     }
 
     #[derive(Deserialize)]
     pub struct CpuStats {
         pub cpu_usage: CpuUsage,
         #[serde(default)]
-        pub system_cpu_usage: u64
+        pub system_cpu_usage: u64,
+        #[serde(default)]
+        pub online_cpus: u64
     }
 
     #[derive(Deserialize)]
@@ -110,25 +131,118 @@ mod contract {
 }
 
 use std::future::Future;
+use std::fs;
 use std::time::Duration;
 use hyper::{body, Body, Client};
+use hyper::client::{HttpConnector, ResponseFuture};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
 use hyperlocal::{UnixClientExt, Uri, UnixConnector};
-use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
 use log::error;
 use tokio::select;
 use tokio::time;
 
+use crate::Config;
+
 pub use contract::*;
 
-static CLIENT: Lazy<Client<UnixConnector, Body>> = Lazy::new(|| { Client::unix() });
+/// The transport used to talk to the Docker daemon, selected once at start-up from the
+/// `DOCKER_HOST` configuration value. Every query is built relative to the chosen endpoint so
+/// the same exporter can scrape a local socket, a remote TCP daemon, or a TLS-protected one.
+enum Transport {
+    Unix { client: Client<UnixConnector, Body>, socket: String },
+    Tcp { client: Client<HttpConnector, Body>, base: String },
+    Tls { client: Client<HttpsConnector<HttpConnector>, Body>, base: String }
+}
+
+impl Transport {
+    fn get(&self, endpoint: &str) -> ResponseFuture {
+        match self {
+            Transport::Unix { client, socket } => client.get(Uri::new(socket, endpoint).into()),
+            Transport::Tcp { client, base } => client.get(format!("{base}{endpoint}").parse().unwrap()),
+            Transport::Tls { client, base } => client.get(format!("{base}{endpoint}").parse().unwrap())
+        }
+    }
+}
+
+static CLIENT: OnceCell<Transport> = OnceCell::new();
+
+/// Build the Docker transport from the configured endpoint and install it for the rest of the
+/// process. `unix://` keeps the `hyperlocal` socket client, while `tcp://`/`http://`/`https://`
+/// switch to a regular `hyper` HTTP(S) client so a remote daemon can be scraped.
+pub fn init(config: &Config) {
+    let endpoint = config.docker_host.as_str();
+
+    let transport = if let Some(socket) = endpoint.strip_prefix("unix://") {
+        Transport::Unix { client: Client::unix(), socket: socket.to_string() }
+    } else if let Some(authority) = endpoint.strip_prefix("tcp://") {
+        let scheme = if config.docker_tls_ca.is_some() || config.docker_tls_cert.is_some() { "https" } else { "http" };
+        build_http_transport(&format!("{scheme}://{authority}"), config)
+    } else if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        build_http_transport(endpoint.trim_end_matches('/'), config)
+    } else {
+        panic!("Unsupported DOCKER_HOST scheme: {endpoint}");
+    };
+
+    CLIENT.set(transport).unwrap_or_else(|_| panic!("Docker transport initialized more than once."));
+}
+
+fn build_http_transport(base: &str, config: &Config) -> Transport {
+    let base = base.trim_end_matches('/').to_string();
+
+    if base.starts_with("https://") {
+        let tls = build_tls_config(config);
+        let connector = HttpsConnectorBuilder::new()
+            .with_tls_config(tls)
+            .https_or_http()
+            .enable_http1()
+            .build();
+        Transport::Tls { client: Client::builder().build(connector), base }
+    } else {
+        Transport::Tcp { client: Client::new(), base }
+    }
+}
+
+fn build_tls_config(config: &Config) -> rustls::ClientConfig {
+    let mut roots = rustls::RootCertStore::empty();
+
+    if let Some(ca) = &config.docker_tls_ca {
+        let pem = fs::read(ca).unwrap_or_else(|e| panic!("Unable to read DOCKER_TLS_CA {ca}: {e}"));
+        for cert in rustls_pemfile::certs(&mut pem.as_slice()).flatten() {
+            roots.add(&rustls::Certificate(cert)).unwrap_or(());
+        }
+    }
+
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+
+    match (&config.docker_tls_cert, &config.docker_tls_key) {
+        (Some(cert), Some(key)) => {
+            let certs = rustls_pemfile::certs(&mut fs::read(cert).unwrap_or_else(|e| panic!("Unable to read DOCKER_TLS_CERT {cert}: {e}")).as_slice())
+                .flatten()
+                .map(rustls::Certificate)
+                .collect::<Vec<_>>();
+            let key = rustls_pemfile::pkcs8_private_keys(&mut fs::read(key).unwrap_or_else(|e| panic!("Unable to read DOCKER_TLS_KEY {key}: {e}")).as_slice())
+                .flatten()
+                .next()
+                .map(rustls::PrivateKey)
+                .expect("DOCKER_TLS_KEY does not contain a usable PKCS#8 private key.");
+            builder.with_client_auth_cert(certs, key).expect("Invalid Docker client certificate/key pair.")
+        }
+        _ => builder.with_no_client_auth()
+    }
+}
 
 async fn get<T: serde::de::DeserializeOwned>(endpoint: &str) -> Option<T> {
+    let client = CLIENT.get().expect("Docker transport has not been initialized.");
+
     select! {
         () = time::sleep(Duration::from_secs(15)) => {
             error!("{} timed out.", endpoint);
             None
         }
-        res = CLIENT.get(Uri::new("/var/run/docker.sock", endpoint).into()) => {
+        res = client.get(endpoint) => {
             match res {
                 Ok(res) => {
                     let status = res.status();
@@ -1,9 +1,11 @@
 use std::net::{SocketAddrV4, Ipv4Addr};
 use std::env::{self, VarError};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use simplelog::{SimpleLogger, Config as LogConfig};
 use tiny_http::{Response, Server};
 use prometheus::{TextEncoder, Encoder};
-use log::{info, LevelFilter};
+use log::{error, info, LevelFilter};
 
 mod docker;
 mod collector;
@@ -12,7 +14,15 @@ pub struct Config {
     port: u16,
     min_log_level: LevelFilter,
     pub collect_image_metrics: bool,
-    pub collect_volume_metrics: bool
+    pub collect_volume_metrics: bool,
+    pub collect_host_metrics: bool,
+    pub docker_host: String,
+    pub docker_tls_ca: Option<String>,
+    pub docker_tls_cert: Option<String>,
+    pub docker_tls_key: Option<String>,
+    pub scrape_interval_seconds: u64,
+    pub max_concurrent_probes: usize,
+    pub expose_labels: Vec<String>
 }
 
 impl Config {
@@ -28,7 +38,15 @@ impl Config {
             port: 9417,
             min_log_level: if Self::is_truthy(env::var("VERBOSE"), cfg!(debug_assertions)) { LevelFilter::Debug } else { LevelFilter::Info },
             collect_image_metrics: Self::is_truthy(env::var("COLLECT_IMAGE_METRICS"), cfg!(debug_assertions)),
-            collect_volume_metrics: Self::is_truthy(env::var("COLLECT_VOLUME_METRICS"), cfg!(debug_assertions))
+            collect_volume_metrics: Self::is_truthy(env::var("COLLECT_VOLUME_METRICS"), cfg!(debug_assertions)),
+            collect_host_metrics: Self::is_truthy(env::var("COLLECT_HOST_METRICS"), false),
+            docker_host: env::var("DOCKER_HOST").unwrap_or_else(|_| "unix:///var/run/docker.sock".to_string()),
+            docker_tls_ca: env::var("DOCKER_TLS_CA").ok(),
+            docker_tls_cert: env::var("DOCKER_TLS_CERT").ok(),
+            docker_tls_key: env::var("DOCKER_TLS_KEY").ok(),
+            scrape_interval_seconds: env::var("SCRAPE_INTERVAL_SECONDS").ok().and_then(|s| s.parse().ok()).filter(|&s| s > 0).unwrap_or(15),
+            max_concurrent_probes: env::var("MAX_CONCURRENT_PROBES").ok().and_then(|s| s.parse().ok()).filter(|&s| s > 0).unwrap_or(8),
+            expose_labels: env::var("EXPOSE_LABELS").map(|s| s.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect()).unwrap_or_default()
         }
     }
 }
@@ -43,10 +61,43 @@ async fn main() {
     let config = Config::new();
     SimpleLogger::init(config.min_log_level, LogConfig::default()).unwrap();
 
+    docker::init(&config);
+
     docker::get_data_usage().await.expect("Test Docker socket query failed.");
 
-    let mut collector = collector::Collector::new();
-    
+    let config = Arc::new(config);
+
+    // The last successfully encoded registry snapshot. `None` until the first probe completes, so
+    // scrapes that arrive before then get a 503 instead of a partial or blocking response.
+    let snapshot: Arc<RwLock<Option<Vec<u8>>>> = Arc::new(RwLock::new(None));
+
+    let scrape_config = config.clone();
+    let scrape_snapshot = snapshot.clone();
+    let scrape_task = tokio::spawn(async move {
+        let mut collector = collector::Collector::new();
+        let mut interval = tokio::time::interval(Duration::from_secs(scrape_config.scrape_interval_seconds));
+
+        loop {
+            interval.tick().await;
+
+            if collector.update(&scrape_config).await {
+                let mut buffer = Vec::new();
+                let encoder = TextEncoder::new();
+                encoder.encode(&prometheus::gather(), &mut buffer).unwrap();
+
+                *scrape_snapshot.write().unwrap() = Some(buffer);
+            }
+        }
+    });
+
+    // The loop above never returns on its own, so the task completing means it panicked. Surface
+    // the failure and exit instead of silently serving a frozen snapshot while the server runs on.
+    tokio::spawn(async move {
+        let outcome = scrape_task.await;
+        error!("Scrape task terminated unexpectedly ({outcome:?}). Exiting.");
+        std::process::exit(1);
+    });
+
     let addr = SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), config.port);
     let server = Server::http(addr).unwrap();
 
@@ -56,14 +107,13 @@ async fn main() {
             continue;
         }
 
-        if collector.update(&config).await {
-            let mut buffer = Vec::new();
-            let encoder = TextEncoder::new();
-            encoder.encode(&prometheus::gather(), &mut buffer).unwrap();
+        // Clone the latest snapshot and release the read lock before writing the response, so a
+        // slow consumer can never hold the lock and block the background scrape task's write.
+        let body = snapshot.read().unwrap().clone();
 
-            req.respond(Response::from_data(buffer)).unwrap_or(());
-        } else {
-            req.respond(Response::empty(408)).unwrap_or(());
+        match body {
+            Some(buffer) => req.respond(Response::from_data(buffer)).unwrap_or(()),
+            None => req.respond(Response::empty(503)).unwrap_or(())
         }
     }
 }
\ No newline at end of file